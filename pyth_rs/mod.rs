@@ -1,10 +1,135 @@
+use std::time::Duration;
+
+use futures_util::Stream;
 use reqwest::{Client, RequestBuilder};
 
 use super::ApiClient;
-use crate::error::Result;
+use crate::error::{Error, Result};
 
 pub mod models;
-use models::PriceUpdate;
+use models::{PriceFeedMetadata, PriceUpdate, PublisherStakeCaps, TwapUpdate};
+
+pub mod provider;
+use provider::PriceProvider;
+
+/// How long to wait before retrying after the SSE connection drops.
+const STREAM_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// Give up on a stream after this many consecutive reconnect failures.
+const STREAM_MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// Hermes only accepts TWAP windows in this range; anything outside it is
+/// rejected before we bother making a request.
+const TWAP_WINDOW_SECONDS: std::ops::RangeInclusive<u64> = 1..=600;
+
+/// A 32-byte Pyth price-feed identifier, rendered over the wire as a 64-char
+/// hex string (optionally `0x`-prefixed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PriceFeedId([u8; 32]);
+
+impl PriceFeedId {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl std::str::FromStr for PriceFeedId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let hex_str = s.strip_prefix("0x").unwrap_or(s);
+        if hex_str.len() > 64 || !hex_str.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(Error::InvalidArgument(format!(
+                "invalid price feed id: {s}"
+            )));
+        }
+
+        let padded = format!("{hex_str:0>64}");
+        let mut bytes = [0u8; 32];
+        for (byte, chunk) in bytes.iter_mut().zip(padded.as_bytes().chunks(2)) {
+            let chunk = std::str::from_utf8(chunk).expect("ascii hex digits");
+            *byte = u8::from_str_radix(chunk, 16)
+                .map_err(|e| Error::InvalidArgument(format!("invalid price feed id {s}: {e}")))?;
+        }
+
+        Ok(Self(bytes))
+    }
+}
+
+impl std::fmt::Display for PriceFeedId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+// Kept so callers migrating off the old `u64` ids don't have to touch call
+// sites that already have a feed id in that form.
+impl From<u64> for PriceFeedId {
+    fn from(id: u64) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&id.to_be_bytes());
+        Self(bytes)
+    }
+}
+
+/// Encoding for the binary VAA payload Hermes returns alongside a price
+/// update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+impl Encoding {
+    fn as_query_str(self) -> &'static str {
+        match self {
+            Encoding::Hex => "hex",
+            Encoding::Base64 => "base64",
+        }
+    }
+}
+
+/// Builds the `encoding`/`parsed`/`ignore_invalid_price_ids` query pairs
+/// shared by every route that accepts them.
+fn encoding_query_pairs(
+    encoding: Encoding,
+    parsed: bool,
+    ignore_invalid_price_ids: bool,
+) -> Vec<(String, String)> {
+    vec![
+        ("encoding".to_string(), encoding.as_query_str().to_string()),
+        ("parsed".to_string(), parsed.to_string()),
+        (
+            "ignore_invalid_price_ids".to_string(),
+            ignore_invalid_price_ids.to_string(),
+        ),
+    ]
+}
+
+/// Category a price feed belongs to, used to filter `get_price_feeds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetType {
+    Crypto,
+    Fx,
+    Equity,
+    Metal,
+    Rates,
+}
+
+impl AssetType {
+    fn as_query_str(self) -> &'static str {
+        match self {
+            AssetType::Crypto => "crypto",
+            AssetType::Fx => "fx",
+            AssetType::Equity => "equity",
+            AssetType::Metal => "metal",
+            AssetType::Rates => "rates",
+        }
+    }
+}
 
 pub struct Pyth {
     base_url: String,
@@ -28,19 +153,116 @@ impl ApiClient for Pyth {
 
 #[derive(Debug)]
 pub struct PriceParams {
-    pub ids: Vec<u64>,
+    pub ids: Vec<PriceFeedId>,
     pub timestamp: u64,
+    pub encoding: Encoding,
+    pub parsed: bool,
+    pub ignore_invalid_price_ids: bool,
 }
 
 impl PriceParams {
-    pub fn new(ids: Vec<u64>, timestamp: u64) -> Self {
-        Self { ids, timestamp }
+    pub fn new(ids: Vec<PriceFeedId>, timestamp: u64) -> Self {
+        Self {
+            ids,
+            timestamp,
+            encoding: Encoding::default(),
+            parsed: false,
+            ignore_invalid_price_ids: false,
+        }
+    }
+
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn with_parsed(mut self, parsed: bool) -> Self {
+        self.parsed = parsed;
+        self
+    }
+
+    pub fn with_ignore_invalid_price_ids(mut self, ignore_invalid_price_ids: bool) -> Self {
+        self.ignore_invalid_price_ids = ignore_invalid_price_ids;
+        self
     }
 }
 
 #[derive(Debug)]
 pub struct LatestParams {
-    pub ids: Vec<u64>,
+    pub ids: Vec<PriceFeedId>,
+    pub encoding: Encoding,
+    pub parsed: bool,
+    pub ignore_invalid_price_ids: bool,
+}
+
+impl LatestParams {
+    pub fn new(ids: Vec<PriceFeedId>) -> Self {
+        Self {
+            ids,
+            encoding: Encoding::default(),
+            parsed: false,
+            ignore_invalid_price_ids: false,
+        }
+    }
+
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn with_parsed(mut self, parsed: bool) -> Self {
+        self.parsed = parsed;
+        self
+    }
+
+    pub fn with_ignore_invalid_price_ids(mut self, ignore_invalid_price_ids: bool) -> Self {
+        self.ignore_invalid_price_ids = ignore_invalid_price_ids;
+        self
+    }
+
+    fn into_query_pairs(self) -> Vec<(String, String)> {
+        let mut query_pairs = self
+            .ids
+            .into_iter()
+            .map(|id| ("ids[]".to_string(), id.to_string()))
+            .collect::<Vec<_>>();
+        query_pairs.extend(encoding_query_pairs(
+            self.encoding,
+            self.parsed,
+            self.ignore_invalid_price_ids,
+        ));
+        query_pairs
+    }
+}
+
+/// Options for `get_latest_publisher_stake_caps`, mirroring the
+/// `encoding`/`parsed`/`ignore_invalid_price_ids` options on `LatestParams`.
+#[derive(Debug, Default)]
+pub struct StakeCapsParams {
+    pub encoding: Encoding,
+    pub parsed: bool,
+    pub ignore_invalid_price_ids: bool,
+}
+
+impl StakeCapsParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn with_parsed(mut self, parsed: bool) -> Self {
+        self.parsed = parsed;
+        self
+    }
+
+    pub fn with_ignore_invalid_price_ids(mut self, ignore_invalid_price_ids: bool) -> Self {
+        self.ignore_invalid_price_ids = ignore_invalid_price_ids;
+        self
+    }
 }
 
 impl Pyth {
@@ -57,22 +279,230 @@ impl Pyth {
         let mut query_pairs = params
             .ids
             .into_iter()
-            // hex formatting still works for u64
-            .map(|id| ("ids[]".to_string(), format!("{id:x}")))
+            .map(|id| ("ids[]".to_string(), id.to_string()))
             .collect::<Vec<_>>();
         query_pairs.push(("timestamp".to_string(), params.timestamp.to_string()));
+        query_pairs.extend(encoding_query_pairs(
+            params.encoding,
+            params.parsed,
+            params.ignore_invalid_price_ids,
+        ));
         req = req.query(&query_pairs);
         self.execute(req).await
     }
 
-    pub async fn get_latest_price(&self, ids: Vec<u64>) -> Result<PriceUpdate> {
+    pub async fn get_latest_price(&self, params: LatestParams) -> Result<PriceUpdate> {
         let route = "updates/price/latest";
         let mut req = self.get(route);
+        req = req.query(&params.into_query_pairs());
+        self.execute(req).await
+    }
+
+    /// Fetches the time-weighted average price over the trailing `window_seconds`,
+    /// saving callers from sampling `get_latest_price` on a loop and averaging it
+    /// themselves.
+    pub async fn get_latest_twap(
+        &self,
+        ids: Vec<PriceFeedId>,
+        window_seconds: u64,
+    ) -> Result<TwapUpdate> {
+        if !TWAP_WINDOW_SECONDS.contains(&window_seconds) {
+            return Err(Error::InvalidArgument(format!(
+                "twap window must be between {} and {} seconds, got {window_seconds}",
+                TWAP_WINDOW_SECONDS.start(),
+                TWAP_WINDOW_SECONDS.end(),
+            )));
+        }
+
+        let route = format!("updates/twap/{window_seconds}/latest");
+        let mut req = self.get(&route);
         let query_pairs = ids
             .into_iter()
-            .map(|id| ("ids[]".to_string(), format!("{id:x}")))
+            .map(|id| ("ids[]".to_string(), id.to_string()))
             .collect::<Vec<_>>();
         req = req.query(&query_pairs);
         self.execute(req).await
     }
+
+    /// Enumerates available price feeds, optionally narrowed to those whose
+    /// symbol contains `query` (case-insensitive) and/or belonging to
+    /// `asset_type`. Useful as a discovery step before calling
+    /// `get_latest_price` with a feed id.
+    pub async fn get_price_feeds(
+        &self,
+        query: Option<String>,
+        asset_type: Option<AssetType>,
+    ) -> Result<Vec<PriceFeedMetadata>> {
+        let route = "price_feeds";
+        let mut req = self.get(route);
+        let mut query_pairs = Vec::new();
+        if let Some(query) = query {
+            query_pairs.push(("query".to_string(), query));
+        }
+        if let Some(asset_type) = asset_type {
+            query_pairs.push(("asset_type".to_string(), asset_type.as_query_str().to_string()));
+        }
+        req = req.query(&query_pairs);
+        self.execute(req).await
+    }
+
+    /// Fetches the latest per-publisher stake caps that gate Oracle
+    /// Integrity Staking rewards.
+    pub async fn get_latest_publisher_stake_caps(
+        &self,
+        params: StakeCapsParams,
+    ) -> Result<PublisherStakeCaps> {
+        let route = "updates/publisher_stake_caps/latest";
+        let mut req = self.get(route);
+        let query_pairs = encoding_query_pairs(
+            params.encoding,
+            params.parsed,
+            params.ignore_invalid_price_ids,
+        );
+        req = req.query(&query_pairs);
+        self.execute(req).await
+    }
+
+    /// Opens the Hermes SSE stream and yields decoded `PriceUpdate`s as they
+    /// arrive, avoiding the latency and rate-limit cost of polling
+    /// `get_latest_price` in a loop. Transient disconnects are retried
+    /// internally up to `STREAM_MAX_RECONNECT_ATTEMPTS` times.
+    pub fn stream_prices(
+        &self,
+        params: LatestParams,
+    ) -> impl Stream<Item = Result<PriceUpdate>> + '_ {
+        let query_pairs = params.into_query_pairs();
+
+        async_stream::try_stream! {
+            // Counts both failed (re)connect attempts and mid-stream
+            // disconnects that never yielded a frame, so a server that
+            // keeps accepting the connection and then immediately dropping
+            // it still gives up after STREAM_MAX_RECONNECT_ATTEMPTS instead
+            // of retrying forever.
+            let mut attempt = 0;
+            loop {
+                let req = self
+                    .get("updates/price/stream")
+                    .header(reqwest::header::ACCEPT, "text/event-stream")
+                    .query(&query_pairs);
+                let response = self.client().execute(req.build()?).await;
+
+                let response = match response {
+                    Ok(response) => response,
+                    Err(_) if attempt < STREAM_MAX_RECONNECT_ATTEMPTS => {
+                        attempt += 1;
+                        tokio::time::sleep(STREAM_RECONNECT_DELAY).await;
+                        continue;
+                    }
+                    Err(err) => Err(err)?,
+                };
+
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let body = response.text().await.unwrap_or_default();
+                    Err(Error::InvalidArgument(format!(
+                        "price stream request failed with status {status}: {body}"
+                    )))?;
+                }
+
+                // Raw bytes are buffered across chunks, and only decoded to
+                // UTF-8 once a full frame has been collected, so a
+                // multi-byte character split across two network chunks
+                // reassembles correctly instead of being replaced with
+                // U+FFFD.
+                let mut buf: Vec<u8> = Vec::new();
+                let mut stream = response.bytes_stream();
+                let mut disconnected = false;
+                let mut read_any_frame = false;
+
+                use futures_util::StreamExt;
+                while let Some(chunk) = stream.next().await {
+                    match chunk {
+                        Ok(bytes) => {
+                            buf.extend_from_slice(&bytes);
+                            while let Some(pos) = find_double_newline(&buf) {
+                                let frame = buf.drain(..=pos + 1).collect::<Vec<_>>();
+                                let frame = std::str::from_utf8(&frame[..pos]).map_err(|e| {
+                                    Error::InvalidArgument(format!(
+                                        "invalid utf-8 in SSE frame: {e}"
+                                    ))
+                                })?;
+                                if let Some(data) = decode_sse_data(frame) {
+                                    let update: PriceUpdate = serde_json::from_str(&data)?;
+                                    read_any_frame = true;
+                                    yield update;
+                                }
+                            }
+                        }
+                        Err(_) => {
+                            disconnected = true;
+                            break;
+                        }
+                    }
+                }
+
+                if !disconnected {
+                    break;
+                }
+                if read_any_frame {
+                    attempt = 0;
+                }
+                if attempt >= STREAM_MAX_RECONNECT_ATTEMPTS {
+                    Err(Error::InvalidArgument(
+                        "price stream disconnected too many times".to_string(),
+                    ))?;
+                }
+                attempt += 1;
+                tokio::time::sleep(STREAM_RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Finds the index of the first byte of a `"\n\n"` frame separator in a raw
+/// byte buffer.
+fn find_double_newline(buf: &[u8]) -> Option<usize> {
+    buf.windows(2).position(|window| window == b"\n\n")
+}
+
+/// Extracts and joins the `data:` lines of a single SSE frame (the text
+/// before a `\n\n` separator), or `None` for frames with no data line
+/// (e.g. SSE comments/keep-alives).
+fn decode_sse_data(frame: &str) -> Option<String> {
+    let lines: Vec<&str> = frame
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| data.trim_start())
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceProvider for Pyth {
+    fn name(&self) -> &str {
+        "pyth-hermes"
+    }
+
+    async fn fetch_latest_price(
+        &self,
+        ids: Vec<PriceFeedId>,
+    ) -> Result<std::collections::HashMap<PriceFeedId, models::Price>> {
+        let params = LatestParams::new(ids).with_parsed(true);
+        let update = self.get_latest_price(params).await?;
+        let parsed = update.parsed.ok_or_else(|| {
+            Error::InvalidArgument("expected parsed price data in response".to_string())
+        })?;
+
+        parsed
+            .into_iter()
+            .map(|update| {
+                let id: PriceFeedId = update.id.parse()?;
+                Ok((id, update.price))
+            })
+            .collect()
+    }
 }
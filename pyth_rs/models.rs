@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Price {
+    pub price: String,
+    pub conf: String,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryUpdate {
+    pub encoding: String,
+    pub data: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParsedPriceUpdate {
+    pub id: String,
+    pub price: Price,
+    pub ema_price: Price,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceUpdate {
+    pub binary: BinaryUpdate,
+    pub parsed: Option<Vec<ParsedPriceUpdate>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParsedTwap {
+    pub id: String,
+    pub start_price: Price,
+    pub end_price: Price,
+    pub twap: Price,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwapUpdate {
+    pub binary: BinaryUpdate,
+    pub parsed: Option<Vec<ParsedTwap>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceFeedAttributes {
+    pub symbol: String,
+    pub description: String,
+    pub asset_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceFeedMetadata {
+    pub id: String,
+    #[serde(flatten)]
+    pub attributes: PriceFeedAttributes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublisherStakeCapEntry {
+    pub publisher: String,
+    pub cap: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParsedPublisherStakeCaps {
+    pub publish_time: i64,
+    pub publisher_stake_caps: Vec<PublisherStakeCapEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublisherStakeCaps {
+    pub binary: BinaryUpdate,
+    pub parsed: Option<Vec<ParsedPublisherStakeCaps>>,
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use futures_util::future::{join, join_all};
+
+use crate::error::{Error, Result};
+
+use super::models::Price;
+use super::PriceFeedId;
+
+/// A source of live prices for a set of feeds. Implemented by `Pyth` itself
+/// so it can be plugged into an `AggregatingClient` alongside other sources.
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    /// A short, stable name for this source (used as a key in aggregated
+    /// results, so it should be unique within a given `AggregatingClient`).
+    fn name(&self) -> &str;
+
+    async fn fetch_latest_price(
+        &self,
+        ids: Vec<PriceFeedId>,
+    ) -> Result<HashMap<PriceFeedId, Price>>;
+}
+
+/// The price chosen for a feed, together with the per-source breakdown that
+/// went into it, so callers can tell whether a source was stale or
+/// divergent.
+#[derive(Debug, Clone)]
+pub struct AggregatedPrice {
+    pub price: Price,
+    pub trusted_source: String,
+    pub sources: HashMap<String, Price>,
+    /// Set when the trusted price deviated from the corroborating median by
+    /// more than the configured tolerance, or when the trusted price is not
+    /// a finite number.
+    pub flagged: bool,
+}
+
+/// Queries a trusted provider plus zero or more corroborating providers for
+/// the same feed and flags the result when the trusted price strays too far
+/// from the corroborating median. This hedges against a single endpoint
+/// being down, stale, or manipulated.
+pub struct AggregatingClient {
+    trusted: Box<dyn PriceProvider>,
+    corroborating: Vec<Box<dyn PriceProvider>>,
+    tolerance: f64,
+}
+
+impl AggregatingClient {
+    /// `tolerance` is a fraction of the corroborating median, e.g. `0.05`
+    /// for 5%.
+    pub fn new(trusted: Box<dyn PriceProvider>, tolerance: f64) -> Self {
+        Self {
+            trusted,
+            corroborating: Vec::new(),
+            tolerance,
+        }
+    }
+
+    pub fn with_source(mut self, provider: Box<dyn PriceProvider>) -> Self {
+        self.corroborating.push(provider);
+        self
+    }
+
+    pub async fn get_latest_price(&self, id: PriceFeedId) -> Result<AggregatedPrice> {
+        // Query the trusted source and every corroborating source
+        // concurrently rather than one at a time.
+        let corroborating_futures = self
+            .corroborating
+            .iter()
+            .map(|provider| provider.fetch_latest_price(vec![id]));
+        let (trusted_prices, corroborating_results) = join(
+            self.trusted.fetch_latest_price(vec![id]),
+            join_all(corroborating_futures),
+        )
+        .await;
+
+        let trusted_prices = trusted_prices?;
+        let trusted_price = trusted_prices.get(&id).cloned().ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "trusted source {} returned no price for {id}",
+                self.trusted.name()
+            ))
+        })?;
+
+        let mut sources = HashMap::new();
+        sources.insert(self.trusted.name().to_string(), trusted_price.clone());
+
+        let mut corroborating_values = Vec::new();
+        for (provider, result) in self.corroborating.iter().zip(corroborating_results) {
+            // A corroborating source being down shouldn't fail the whole
+            // aggregate; we just lose that source's vote.
+            let Ok(prices) = result else {
+                continue;
+            };
+            let Some(price) = prices.get(&id) else {
+                continue;
+            };
+            // A source returning a non-finite value (e.g. "NaN") shouldn't
+            // be allowed to corrupt the median or panic the sort below.
+            if let Some(value) = parse_finite(&price.price) {
+                corroborating_values.push(value);
+            }
+            sources.insert(provider.name().to_string(), price.clone());
+        }
+
+        // A non-finite/unparseable trusted price is itself a divergence from
+        // any sane corroborating set, so flag it rather than silently
+        // falling back to 0.0 (which could mask the bad price entirely).
+        let trusted_value = parse_finite(&trusted_price.price);
+        let flagged = match (trusted_value, corroborating_values.is_empty()) {
+            (None, _) => true,
+            (Some(_), true) => false,
+            (Some(trusted_value), false) => {
+                let median = median(&mut corroborating_values);
+                let deviation = if median.abs() > f64::EPSILON {
+                    ((trusted_value - median) / median).abs()
+                } else {
+                    0.0
+                };
+                deviation > self.tolerance
+            }
+        };
+
+        Ok(AggregatedPrice {
+            price: trusted_price,
+            trusted_source: self.trusted.name().to_string(),
+            sources,
+            flagged,
+        })
+    }
+}
+
+/// Parses a price string to `f64`, rejecting values that parse but aren't
+/// finite (`NaN`/`inf`), which would otherwise panic the median sort or
+/// silently defeat the deviation check.
+fn parse_finite(value: &str) -> Option<f64> {
+    value.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    // Callers only push values that passed `parse_finite`, so `partial_cmp`
+    // never sees NaN here.
+    values.sort_by(|a, b| a.partial_cmp(b).expect("values are pre-filtered to finite"));
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}